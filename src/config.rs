@@ -0,0 +1,333 @@
+// Hot-reloadable node configuration.
+//
+// Reloading a running node is split into two steps on purpose: `parse`
+// turns the settings file into a typed `NodeConfig` and can fail on its
+// own, while `apply` decides whether the new config is safe to adopt live.
+// If either step fails, the previously-applied config stays in force --
+// a bad edit to the settings file (or an attempt to change a field that
+// requires a restart) never leaves the node half-reconfigured.
+
+use std::fs;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Fields that cannot be changed without restarting the node. Anything not
+/// listed here is safe to apply live.
+const RESTART_ONLY_FIELDS: &[&str] = &["data_dir", "network_id", "private_key_file", "reload_bind"];
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct NodeConfig {
+  /// Where the node stores its chain state. Restart-only: changing it out
+  /// from under a running node would desync in-memory and on-disk state.
+  pub data_dir: PathBuf,
+  /// Network/genesis identifier. Restart-only for the same reason.
+  pub network_id: u32,
+  /// Path to the node's own signing key. Restart-only: the node identity
+  /// is assumed fixed for the lifetime of the process.
+  pub private_key_file: PathBuf,
+  /// Address the `POST /reload` endpoint listens on. Restart-only: the
+  /// endpoint is what you'd use to push a live reload in the first place,
+  /// so rebinding it live would need a second, independent way to reach it.
+  #[serde(default = "default_reload_bind")]
+  pub reload_bind: SocketAddr,
+
+  /// Whether the node should mine blocks.
+  #[serde(default)]
+  pub mine: bool,
+  /// Maximum number of simultaneous peer connections.
+  #[serde(default = "default_max_peers")]
+  pub max_peers: usize,
+  /// Addresses the HTTP API listens on.
+  #[serde(default = "default_api_bind")]
+  pub api_bind: Vec<SocketAddr>,
+  /// Per-block mana limit.
+  #[serde(default = "default_max_mana")]
+  pub max_mana: u64,
+  /// Per-block space limit, in bytes.
+  #[serde(default = "default_max_space")]
+  pub max_space: u64,
+}
+
+fn default_reload_bind() -> SocketAddr {
+  "127.0.0.1:8001".parse().unwrap()
+}
+
+fn default_max_peers() -> usize {
+  32
+}
+
+fn default_api_bind() -> Vec<SocketAddr> {
+  vec!["127.0.0.1:8000".parse().unwrap()]
+}
+
+fn default_max_mana() -> u64 {
+  100_000
+}
+
+fn default_max_space() -> u64 {
+  100_000_000
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+  /// The settings file could not be read or did not parse as a `NodeConfig`.
+  Parse(String),
+  /// The new config differs from the live one in fields that require a
+  /// restart; none of it was applied.
+  RequiresRestart(Vec<&'static str>),
+}
+
+impl std::fmt::Display for ConfigError {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    match self {
+      ConfigError::Parse(message) => write!(f, "could not parse config: {}", message),
+      ConfigError::RequiresRestart(fields) => {
+        write!(f, "config change requires a restart for: {}", fields.join(", "))
+      }
+    }
+  }
+}
+
+/// Parses a settings file into a typed [`NodeConfig`]. Kept separate from
+/// [`apply`] so a syntax error in the file never touches the live config.
+pub fn parse(path: &Path) -> Result<NodeConfig, ConfigError> {
+  let text = fs::read_to_string(path).map_err(|err| ConfigError::Parse(err.to_string()))?;
+  toml::from_str(&text).map_err(|err| ConfigError::Parse(err.to_string()))
+}
+
+/// Names of the top-level fields that differ between two configs.
+fn changed_fields(old: &NodeConfig, new: &NodeConfig) -> Vec<&'static str> {
+  let mut changed = Vec::new();
+  if old.data_dir != new.data_dir {
+    changed.push("data_dir");
+  }
+  if old.network_id != new.network_id {
+    changed.push("network_id");
+  }
+  if old.private_key_file != new.private_key_file {
+    changed.push("private_key_file");
+  }
+  if old.reload_bind != new.reload_bind {
+    changed.push("reload_bind");
+  }
+  if old.mine != new.mine {
+    changed.push("mine");
+  }
+  if old.max_peers != new.max_peers {
+    changed.push("max_peers");
+  }
+  if old.api_bind != new.api_bind {
+    changed.push("api_bind");
+  }
+  if old.max_mana != new.max_mana {
+    changed.push("max_mana");
+  }
+  if old.max_space != new.max_space {
+    changed.push("max_space");
+  }
+  changed
+}
+
+/// The node's live, currently-applied configuration.
+pub struct LiveConfig(Mutex<NodeConfig>);
+
+impl LiveConfig {
+  pub fn new(initial: NodeConfig) -> Arc<LiveConfig> {
+    Arc::new(LiveConfig(Mutex::new(initial)))
+  }
+
+  pub fn snapshot(&self) -> NodeConfig {
+    self.0.lock().unwrap().clone()
+  }
+
+  /// Applies `new` if and only if every changed field is safe to change
+  /// live. Returns the list of fields that were changed.
+  fn apply(&self, new: NodeConfig) -> Result<Vec<&'static str>, ConfigError> {
+    let mut live = self.0.lock().unwrap();
+    let changed = changed_fields(&live, &new);
+    let restart_only: Vec<&'static str> =
+      changed.iter().copied().filter(|field| RESTART_ONLY_FIELDS.contains(field)).collect();
+    if !restart_only.is_empty() {
+      return Err(ConfigError::RequiresRestart(restart_only));
+    }
+    *live = new;
+    Ok(changed)
+  }
+}
+
+/// Parses `path` and applies it to `live`. On success, logs which keys
+/// changed (if any) and returns that list. On failure -- either a parse
+/// error or a restart-only field changing -- `live` is left untouched.
+pub fn reload(path: &Path, live: &LiveConfig) -> Result<Vec<&'static str>, ConfigError> {
+  let new = parse(path)?;
+  match live.apply(new) {
+    Ok(changed) => {
+      if changed.is_empty() {
+        log::info!("config reload: no changes");
+      } else {
+        log::info!("config reload: changed {}", changed.join(", "));
+      }
+      Ok(changed)
+    }
+    Err(err) => {
+      log::warn!("config reload rejected, keeping previous config: {}", err);
+      Err(err)
+    }
+  }
+}
+
+/// Watches `path`'s mtime on a background thread and calls [`reload`]
+/// whenever it changes, so edits to the settings file are picked up
+/// without needing `SIGHUP` or the `/reload` endpoint.
+pub fn spawn_file_watcher(path: PathBuf, live: Arc<LiveConfig>) {
+  std::thread::spawn(move || {
+    let mut last_modified = fs::metadata(&path).and_then(|meta| meta.modified()).ok();
+    loop {
+      std::thread::sleep(std::time::Duration::from_secs(2));
+      let modified = match fs::metadata(&path).and_then(|meta| meta.modified()) {
+        Ok(modified) => modified,
+        Err(err) => {
+          log::warn!("could not stat config file {}: {}", path.display(), err);
+          continue;
+        }
+      };
+      if last_modified != Some(modified) {
+        last_modified = Some(modified);
+        let _ = reload(&path, &live);
+      }
+    }
+  });
+}
+
+/// Installs a `SIGHUP` handler that reloads `path` into `live` whenever the
+/// node process receives it, mirroring the common daemon convention.
+pub fn install_sighup_handler(path: PathBuf, live: Arc<LiveConfig>) -> Result<(), std::io::Error> {
+  let mut signals = signal_hook::iterator::Signals::new([signal_hook::consts::SIGHUP])?;
+  std::thread::spawn(move || {
+    for _ in signals.forever() {
+      log::info!("received SIGHUP, reloading config from {}", path.display());
+      let _ = reload(&path, &live);
+    }
+  });
+  Ok(())
+}
+
+/// Response body for the `POST /reload` API endpoint.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct ReloadResponse {
+  pub changed: Vec<String>,
+}
+
+/// Handles a `POST /reload` request: re-reads and re-applies `path` through
+/// [`reload`], the same function the `SIGHUP` handler and the file watcher
+/// call, so all three share one code path.
+pub fn handle_reload_request(
+  path: &Path,
+  live: &LiveConfig,
+) -> Result<ReloadResponse, ConfigError> {
+  let changed = reload(path, live)?;
+  Ok(ReloadResponse { changed: changed.into_iter().map(String::from).collect() })
+}
+
+/// Serves `POST /reload` on `bind`, calling [`handle_reload_request`] for
+/// every request. Runs on its own background thread and its own tiny HTTP
+/// server rather than going through the node's main API router, so the
+/// config subsystem stays usable even before that router exists in this
+/// tree. Returns the address actually bound (useful when `bind`'s port is
+/// `0`), or `None` if the port could not be bound.
+pub fn spawn_reload_server(bind: SocketAddr, config_path: PathBuf, live: Arc<LiveConfig>) -> Option<SocketAddr> {
+  let server = match tiny_http::Server::http(bind) {
+    Ok(server) => server,
+    Err(err) => {
+      log::warn!("could not start the /reload endpoint on {}: {}", bind, err);
+      return None;
+    }
+  };
+  let bound_addr = server.server_addr().to_ip().unwrap_or(bind);
+  std::thread::spawn(move || {
+    for request in server.incoming_requests() {
+      if request.method() != &tiny_http::Method::Post || request.url() != "/reload" {
+        let _ = request.respond(tiny_http::Response::empty(404));
+        continue;
+      }
+      let (status, body) = match handle_reload_request(&config_path, &live) {
+        Ok(response) => (200, serde_json::to_string(&response).unwrap_or_default()),
+        Err(err) => (400, format!("{{\"error\":{:?}}}", err.to_string())),
+      };
+      let _ = request.respond(tiny_http::Response::from_string(body).with_status_code(status));
+    }
+  });
+  Some(bound_addr)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn base_config() -> NodeConfig {
+    NodeConfig {
+      data_dir: PathBuf::from("/tmp/kindelia"),
+      network_id: 1,
+      private_key_file: PathBuf::from("/tmp/kindelia/key"),
+      reload_bind: "127.0.0.1:8001".parse().unwrap(),
+      mine: false,
+      max_peers: 32,
+      api_bind: vec!["127.0.0.1:8000".parse().unwrap()],
+      max_mana: 100_000,
+      max_space: 100_000_000,
+    }
+  }
+
+  #[test]
+  fn applies_hot_reloadable_changes() {
+    let live = LiveConfig::new(base_config());
+    let mut new = base_config();
+    new.mine = true;
+    new.max_peers = 64;
+    let changed = live.apply(new).unwrap();
+    assert_eq!(changed, vec!["mine", "max_peers"]);
+    assert!(live.snapshot().mine);
+  }
+
+  #[test]
+  fn rejects_restart_only_changes_without_applying() {
+    let live = LiveConfig::new(base_config());
+    let mut new = base_config();
+    new.network_id = 2;
+    new.mine = true;
+    let err = live.apply(new).unwrap_err();
+    match err {
+      ConfigError::RequiresRestart(fields) => assert_eq!(fields, vec!["network_id"]),
+      other => panic!("expected RequiresRestart, got {:?}", other),
+    }
+    // the rejected change must not have been partially applied
+    assert!(!live.snapshot().mine);
+  }
+
+  #[test]
+  fn reload_endpoint_applies_a_posted_reload() {
+    let mut config_path = std::env::temp_dir();
+    config_path.push(format!("kindelia-config-reload-test-{:?}.toml", std::thread::current().id()));
+    let mut updated = base_config();
+    updated.mine = true;
+    fs::write(&config_path, toml::to_string(&updated).unwrap()).unwrap();
+
+    let live = LiveConfig::new(base_config());
+    let bind: SocketAddr = "127.0.0.1:0".parse().unwrap();
+    let bound_addr =
+      spawn_reload_server(bind, config_path.clone(), live.clone()).expect("could not bind /reload server");
+
+    let response = reqwest::blocking::Client::new()
+      .post(format!("http://{}/reload", bound_addr))
+      .send()
+      .unwrap();
+    assert!(response.status().is_success());
+
+    let body: ReloadResponse = response.json().unwrap();
+    assert_eq!(body.changed, vec!["mine"]);
+    assert!(live.snapshot().mine);
+
+    fs::remove_file(&config_path).unwrap();
+  }
+}
@@ -0,0 +1,168 @@
+// Key-derivation and signing helpers shared by the `key`, `sign` and
+// `verify` commands.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::time::Instant;
+
+use rand::rngs::OsRng;
+use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
+use tiny_keccak::{Hasher, Keccak};
+
+use crate::hvm;
+
+/// Number of keccak256 stretching rounds applied to a brain-wallet passphrase.
+///
+/// Chosen to make brute-forcing a short passphrase noticeably expensive
+/// without making key derivation annoying to wait for interactively.
+pub const BRAIN_WALLET_ROUNDS: usize = 16384;
+
+/// Derives a deterministic secp256k1 signing key from a human-memorable
+/// passphrase ("brain wallet").
+///
+/// `seed` starts as the passphrase bytes and is repeatedly rehashed as
+/// `seed = keccak256(seed ++ phrase)` for [`BRAIN_WALLET_ROUNDS`] rounds.
+/// The final 32 bytes are interpreted as a secp256k1 scalar; if that
+/// scalar is zero or falls outside the curve order (astronomically
+/// unlikely, but `SecretKey::from_slice` is the authority on it), the
+/// seed is hashed once more and the whole stretch is retried.
+pub fn derive_brain_key(phrase: &str) -> SecretKey {
+  let phrase_bytes = phrase.as_bytes();
+  let mut seed = phrase_bytes.to_vec();
+  loop {
+    for _ in 0 .. BRAIN_WALLET_ROUNDS {
+      seed = keccak256(&seed, phrase_bytes);
+    }
+    match SecretKey::from_slice(&seed) {
+      Ok(secret_key) => return secret_key,
+      Err(_) => seed = keccak256(&seed, phrase_bytes),
+    }
+  }
+}
+
+fn keccak256(a: &[u8], b: &[u8]) -> Vec<u8> {
+  let mut hasher = Keccak::v256();
+  hasher.update(a);
+  hasher.update(b);
+  let mut digest = [0u8; 32];
+  hasher.finalize(&mut digest);
+  digest.to_vec()
+}
+
+/// Derives the public key matching a secret key.
+pub fn public_key(secret_key: &SecretKey) -> PublicKey {
+  let secp = Secp256k1::new();
+  PublicKey::from_secret_key(&secp, secret_key)
+}
+
+/// Derives the Kindelia account [`hvm::Name`] owned by a public key.
+///
+/// This is the same derivation the node uses to attribute `sign`ed
+/// statements to an account: the uncompressed public key is hashed with
+/// keccak256 and the low 128 bits of the digest become the `Name`.
+pub fn account_name(public_key: &PublicKey) -> hvm::Name {
+  let mut hasher = Keccak::v256();
+  hasher.update(&public_key.serialize_uncompressed());
+  let mut digest = [0u8; 32];
+  hasher.finalize(&mut digest);
+  let mut low_bytes = [0u8; 16];
+  low_bytes.copy_from_slice(&digest[16 ..]);
+  hvm::Name::from_u128_unchecked(u128::from_be_bytes(low_bytes))
+}
+
+/// Signs a 32-byte message hash, returning a 65-byte recoverable signature
+/// (`r ++ s ++ recovery_id`) in the format the `sign` command embeds in a
+/// statement's `sign { ... }` field.
+pub fn sign_hash(secret_key: &SecretKey, hash: &[u8; 32]) -> [u8; 65] {
+  let secp = Secp256k1::new();
+  let message = Message::from_slice(hash).expect("hash is 32 bytes");
+  let signature = secp.sign_ecdsa_recoverable(&message, secret_key);
+  let (recovery_id, bytes) = signature.serialize_compact();
+  let mut out = [0u8; 65];
+  out[.. 64].copy_from_slice(&bytes);
+  out[64] = recovery_id.to_i32() as u8;
+  out
+}
+
+/// Recovers the public key that produced a 65-byte recoverable signature
+/// over a 32-byte message hash.
+pub fn recover_public_key(
+  hash: &[u8; 32],
+  signature: &[u8; 65],
+) -> Result<PublicKey, secp256k1::Error> {
+  let secp = Secp256k1::new();
+  let recovery_id = secp256k1::ecdsa::RecoveryId::from_i32(signature[64] as i32)?;
+  let recoverable_signature =
+    secp256k1::ecdsa::RecoverableSignature::from_compact(&signature[.. 64], recovery_id)?;
+  let message = Message::from_slice(hash).expect("hash is 32 bytes");
+  secp.recover_ecdsa(&message, &recoverable_signature)
+}
+
+/// A keypair whose account Name matched a vanity prefix search.
+pub struct PrefixMatch {
+  pub secret_key: SecretKey,
+  pub public_key: PublicKey,
+  pub name: hvm::Name,
+  pub attempts: u64,
+}
+
+/// Brute-forces random secp256k1 keys across `threads` worker threads until
+/// one derives an account Name whose base-63 rendering starts with `prefix`.
+///
+/// All workers share an atomic "found" flag so every worker notices a hit
+/// within one iteration and stops promptly, instead of racing to generate
+/// more matches than needed. Progress (attempts/sec) is reported to stderr
+/// once a second so users can gauge feasibility of longer prefixes.
+pub fn search_prefix(prefix: &str, threads: usize) -> PrefixMatch {
+  let prefix = prefix.to_string();
+  let found = Arc::new(AtomicBool::new(false));
+  let attempts = Arc::new(AtomicU64::new(0));
+  let (result_tx, result_rx) = mpsc::channel();
+  let started_at = Instant::now();
+
+  let mut workers = Vec::with_capacity(threads);
+  for _ in 0 .. threads {
+    let prefix = prefix.clone();
+    let found = Arc::clone(&found);
+    let attempts = Arc::clone(&attempts);
+    let result_tx = result_tx.clone();
+    workers.push(std::thread::spawn(move || {
+      let secp = Secp256k1::new();
+      let mut rng = OsRng;
+      while !found.load(Ordering::Relaxed) {
+        let secret_key = SecretKey::new(&mut rng);
+        let public_key = PublicKey::from_secret_key(&secp, &secret_key);
+        let name = account_name(&public_key);
+        let attempt_count = attempts.fetch_add(1, Ordering::Relaxed) + 1;
+        if name.to_base63().starts_with(&prefix) && !found.swap(true, Ordering::Relaxed) {
+          let _ = result_tx.send(PrefixMatch {
+            secret_key,
+            public_key,
+            name,
+            attempts: attempt_count,
+          });
+        }
+      }
+    }));
+  }
+  drop(result_tx);
+
+  let reporter_found = Arc::clone(&found);
+  let reporter_attempts = Arc::clone(&attempts);
+  let reporter = std::thread::spawn(move || {
+    while !reporter_found.load(Ordering::Relaxed) {
+      std::thread::sleep(std::time::Duration::from_secs(1));
+      let elapsed = started_at.elapsed().as_secs_f64().max(0.001);
+      let rate = reporter_attempts.load(Ordering::Relaxed) as f64 / elapsed;
+      eprintln!("{:.0} attempts/sec", rate);
+    }
+  });
+
+  let result = result_rx.recv().expect("no worker thread produced a match");
+  for worker in workers {
+    let _ = worker.join();
+  }
+  let _ = reporter.join();
+  result
+}
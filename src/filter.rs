@@ -0,0 +1,549 @@
+// Small expression language for `kindelia get --filter`, letting users query
+// and project decoded API responses without piping to `jq` or similar.
+//
+// Pipeline: tokenize -> parse (recursive descent) -> evaluate over a
+// `serde_json::Value`, producing a `Value`.
+
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+  Int(i128),
+  UInt(u128),
+  Str(String),
+  Bool(bool),
+  Array(Vec<Value>),
+  Nil,
+}
+
+impl fmt::Display for Value {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Value::Int(n) => write!(f, "{}", n),
+      Value::UInt(n) => write!(f, "{}", n),
+      Value::Str(s) => write!(f, "{}", s),
+      Value::Bool(b) => write!(f, "{}", b),
+      Value::Array(items) => {
+        let rendered: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+        write!(f, "{}", rendered.join("\n"))
+      }
+      Value::Nil => write!(f, ""),
+    }
+  }
+}
+
+impl Value {
+  pub fn is_truthy(&self) -> bool {
+    match self {
+      Value::Bool(b) => *b,
+      Value::Nil => false,
+      _ => true,
+    }
+  }
+
+  fn from_json(json: &serde_json::Value) -> Value {
+    match json {
+      serde_json::Value::Null => Value::Nil,
+      serde_json::Value::Bool(b) => Value::Bool(*b),
+      serde_json::Value::Number(n) => {
+        if let Some(n) = n.as_u64() {
+          Value::UInt(n as u128)
+        } else if let Some(n) = n.as_i64() {
+          Value::Int(n as i128)
+        } else {
+          Value::Str(n.to_string())
+        }
+      }
+      serde_json::Value::String(s) => Value::Str(s.clone()),
+      serde_json::Value::Array(items) => {
+        Value::Array(items.iter().map(Value::from_json).collect())
+      }
+      serde_json::Value::Object(_) => Value::Nil,
+    }
+  }
+}
+
+#[derive(Debug)]
+pub struct FilterError(pub String);
+
+impl fmt::Display for FilterError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "filter error: {}", self.0)
+  }
+}
+
+// -- Tokenizer ---------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+  Ident(String),
+  Number(String),
+  Str(String),
+  And,
+  Or,
+  Not,
+  Eq,
+  Neq,
+  Lt,
+  Le,
+  Gt,
+  Ge,
+  Pipe,
+  Dot,
+  LParen,
+  RParen,
+  LBracket,
+  RBracket,
+  Comma,
+  True,
+  False,
+  Eof,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, FilterError> {
+  let chars: Vec<char> = input.chars().collect();
+  let mut tokens = Vec::new();
+  let mut i = 0;
+  while i < chars.len() {
+    let c = chars[i];
+    match c {
+      ' ' | '\t' | '\n' => i += 1,
+      '(' => {
+        tokens.push(Token::LParen);
+        i += 1;
+      }
+      ')' => {
+        tokens.push(Token::RParen);
+        i += 1;
+      }
+      '[' => {
+        tokens.push(Token::LBracket);
+        i += 1;
+      }
+      ']' => {
+        tokens.push(Token::RBracket);
+        i += 1;
+      }
+      ',' => {
+        tokens.push(Token::Comma);
+        i += 1;
+      }
+      '.' => {
+        tokens.push(Token::Dot);
+        i += 1;
+      }
+      '&' if chars.get(i + 1) == Some(&'&') => {
+        tokens.push(Token::And);
+        i += 2;
+      }
+      '|' if chars.get(i + 1) == Some(&'|') => {
+        tokens.push(Token::Or);
+        i += 2;
+      }
+      '|' => {
+        tokens.push(Token::Pipe);
+        i += 1;
+      }
+      '=' if chars.get(i + 1) == Some(&'=') => {
+        tokens.push(Token::Eq);
+        i += 2;
+      }
+      '!' if chars.get(i + 1) == Some(&'=') => {
+        tokens.push(Token::Neq);
+        i += 2;
+      }
+      '!' => {
+        tokens.push(Token::Not);
+        i += 1;
+      }
+      '<' if chars.get(i + 1) == Some(&'=') => {
+        tokens.push(Token::Le);
+        i += 2;
+      }
+      '<' => {
+        tokens.push(Token::Lt);
+        i += 1;
+      }
+      '>' if chars.get(i + 1) == Some(&'=') => {
+        tokens.push(Token::Ge);
+        i += 2;
+      }
+      '>' => {
+        tokens.push(Token::Gt);
+        i += 1;
+      }
+      '"' => {
+        let mut string = String::new();
+        i += 1;
+        while i < chars.len() && chars[i] != '"' {
+          string.push(chars[i]);
+          i += 1;
+        }
+        if i >= chars.len() {
+          return Err(FilterError("unterminated string literal".into()));
+        }
+        i += 1;
+        tokens.push(Token::Str(string));
+      }
+      c if c.is_ascii_digit() => {
+        let start = i;
+        while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '_') {
+          i += 1;
+        }
+        tokens.push(Token::Number(chars[start .. i].iter().filter(|c| **c != '_').collect()));
+      }
+      c if c.is_alphabetic() || c == '_' => {
+        let start = i;
+        while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+          i += 1;
+        }
+        let ident: String = chars[start .. i].iter().collect();
+        tokens.push(match ident.as_str() {
+          "true" => Token::True,
+          "false" => Token::False,
+          _ => Token::Ident(ident),
+        });
+      }
+      _ => return Err(FilterError(format!("unexpected character '{}'", c))),
+    }
+  }
+  tokens.push(Token::Eof);
+  Ok(tokens)
+}
+
+// -- Parser -------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+pub enum Expr {
+  Literal(Literal),
+  Field(String),
+  FieldAccess(Box<Expr>, String),
+  Index(Box<Expr>, Box<Expr>),
+  Not(Box<Expr>),
+  BinOp(BinOp, Box<Expr>, Box<Expr>),
+  Pipe(Box<Expr>, String, Vec<Expr>),
+}
+
+#[derive(Debug, Clone)]
+pub enum Literal {
+  Int(i128),
+  Str(String),
+  Bool(bool),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinOp {
+  And,
+  Or,
+  Eq,
+  Neq,
+  Lt,
+  Le,
+  Gt,
+  Ge,
+}
+
+struct Parser {
+  tokens: Vec<Token>,
+  pos: usize,
+}
+
+impl Parser {
+  fn peek(&self) -> &Token {
+    &self.tokens[self.pos]
+  }
+
+  fn next(&mut self) -> Token {
+    let token = self.tokens[self.pos].clone();
+    self.pos += 1;
+    token
+  }
+
+  fn expect(&mut self, token: Token) -> Result<(), FilterError> {
+    if self.peek() == &token {
+      self.pos += 1;
+      Ok(())
+    } else {
+      Err(FilterError(format!("expected {:?}, found {:?}", token, self.peek())))
+    }
+  }
+
+  // expr := or_expr
+  fn parse_expr(&mut self) -> Result<Expr, FilterError> {
+    self.parse_or()
+  }
+
+  fn parse_or(&mut self) -> Result<Expr, FilterError> {
+    let mut left = self.parse_and()?;
+    while self.peek() == &Token::Or {
+      self.next();
+      let right = self.parse_and()?;
+      left = Expr::BinOp(BinOp::Or, Box::new(left), Box::new(right));
+    }
+    Ok(left)
+  }
+
+  fn parse_and(&mut self) -> Result<Expr, FilterError> {
+    let mut left = self.parse_comparison()?;
+    while self.peek() == &Token::And {
+      self.next();
+      let right = self.parse_comparison()?;
+      left = Expr::BinOp(BinOp::And, Box::new(left), Box::new(right));
+    }
+    Ok(left)
+  }
+
+  fn parse_comparison(&mut self) -> Result<Expr, FilterError> {
+    let left = self.parse_pipe()?;
+    let op = match self.peek() {
+      Token::Eq => BinOp::Eq,
+      Token::Neq => BinOp::Neq,
+      Token::Lt => BinOp::Lt,
+      Token::Le => BinOp::Le,
+      Token::Gt => BinOp::Gt,
+      Token::Ge => BinOp::Ge,
+      _ => return Ok(left),
+    };
+    self.next();
+    let right = self.parse_pipe()?;
+    Ok(Expr::BinOp(op, Box::new(left), Box::new(right)))
+  }
+
+  // a.b.c | func | func(args)
+  fn parse_pipe(&mut self) -> Result<Expr, FilterError> {
+    let mut expr = self.parse_unary()?;
+    while self.peek() == &Token::Pipe {
+      self.next();
+      let name = match self.next() {
+        Token::Ident(name) => name,
+        other => return Err(FilterError(format!("expected function name, found {:?}", other))),
+      };
+      let mut args = Vec::new();
+      if self.peek() == &Token::LParen {
+        self.next();
+        if self.peek() != &Token::RParen {
+          args.push(self.parse_expr()?);
+          while self.peek() == &Token::Comma {
+            self.next();
+            args.push(self.parse_expr()?);
+          }
+        }
+        self.expect(Token::RParen)?;
+      }
+      expr = Expr::Pipe(Box::new(expr), name, args);
+    }
+    Ok(expr)
+  }
+
+  fn parse_unary(&mut self) -> Result<Expr, FilterError> {
+    if self.peek() == &Token::Not {
+      self.next();
+      return Ok(Expr::Not(Box::new(self.parse_unary()?)));
+    }
+    self.parse_call_or_field()
+  }
+
+  // ident ( args... ) | ident . ident . ident
+  fn parse_call_or_field(&mut self) -> Result<Expr, FilterError> {
+    let mut expr = self.parse_primary()?;
+    loop {
+      match self.peek() {
+        Token::Dot => {
+          self.next();
+          let field = match self.next() {
+            Token::Ident(name) => name,
+            other => return Err(FilterError(format!("expected field name, found {:?}", other))),
+          };
+          expr = Expr::FieldAccess(Box::new(expr), field);
+        }
+        Token::LBracket => {
+          self.next();
+          let index = self.parse_expr()?;
+          self.expect(Token::RBracket)?;
+          expr = Expr::Index(Box::new(expr), Box::new(index));
+        }
+        Token::LParen if matches!(expr, Expr::Field(_)) => {
+          // `ident(args)` is sugar for `ident | func(args)`-less direct calls,
+          // e.g. `contains(reg.list, "Foo")`.
+          let name = match expr {
+            Expr::Field(name) => name,
+            _ => unreachable!(),
+          };
+          self.next();
+          let mut args = Vec::new();
+          if self.peek() != &Token::RParen {
+            args.push(self.parse_expr()?);
+            while self.peek() == &Token::Comma {
+              self.next();
+              args.push(self.parse_expr()?);
+            }
+          }
+          self.expect(Token::RParen)?;
+          if args.is_empty() {
+            return Err(FilterError(format!("'{}' expects at least one argument", name)));
+          }
+          let first = args.remove(0);
+          expr = Expr::Pipe(Box::new(first), name, args);
+        }
+        _ => break,
+      }
+    }
+    Ok(expr)
+  }
+
+  fn parse_primary(&mut self) -> Result<Expr, FilterError> {
+    match self.next() {
+      Token::Number(digits) => {
+        let n: i128 = digits.parse().map_err(|_| FilterError(format!("invalid number '{}'", digits)))?;
+        Ok(Expr::Literal(Literal::Int(n)))
+      }
+      Token::Str(s) => Ok(Expr::Literal(Literal::Str(s))),
+      Token::True => Ok(Expr::Literal(Literal::Bool(true))),
+      Token::False => Ok(Expr::Literal(Literal::Bool(false))),
+      Token::Ident(name) => Ok(Expr::Field(name)),
+      Token::LParen => {
+        let expr = self.parse_expr()?;
+        self.expect(Token::RParen)?;
+        Ok(expr)
+      }
+      other => Err(FilterError(format!("unexpected token {:?}", other))),
+    }
+  }
+}
+
+pub fn parse(input: &str) -> Result<Expr, FilterError> {
+  let tokens = tokenize(input)?;
+  let mut parser = Parser { tokens, pos: 0 };
+  let expr = parser.parse_expr()?;
+  if parser.peek() != &Token::Eof {
+    return Err(FilterError(format!("unexpected trailing token {:?}", parser.peek())));
+  }
+  Ok(expr)
+}
+
+// -- Evaluator ----------------------------------------------------------------
+
+pub fn eval(expr: &Expr, root: &serde_json::Value) -> Result<Value, FilterError> {
+  match expr {
+    Expr::Literal(Literal::Int(n)) => Ok(Value::Int(*n)),
+    Expr::Literal(Literal::Str(s)) => Ok(Value::Str(s.clone())),
+    Expr::Literal(Literal::Bool(b)) => Ok(Value::Bool(*b)),
+    Expr::Field(name) => Ok(Value::from_json(root.get(name).unwrap_or(&serde_json::Value::Null))),
+    Expr::FieldAccess(base, field) => {
+      let base_json = eval_json(base, root)?;
+      Ok(Value::from_json(base_json.get(field).unwrap_or(&serde_json::Value::Null)))
+    }
+    Expr::Index(base, index) => {
+      let base_json = eval_json(base, root)?;
+      let index = eval_index(index, root)?;
+      Ok(Value::from_json(base_json.get(index).unwrap_or(&serde_json::Value::Null)))
+    }
+    Expr::Not(inner) => Ok(Value::Bool(!eval(inner, root)?.is_truthy())),
+    Expr::BinOp(op, left, right) => eval_binop(*op, left, right, root),
+    Expr::Pipe(base, func, args) => {
+      let base_value = eval(base, root)?;
+      let arg_values: Result<Vec<Value>, FilterError> =
+        args.iter().map(|arg| eval(arg, root)).collect();
+      call_builtin(func, base_value, arg_values?)
+    }
+  }
+}
+
+// `FieldAccess` needs to chain through raw JSON objects, not the flattened
+// `Value`, so object fields stay reachable beyond one level of nesting.
+fn eval_json<'a>(expr: &Expr, root: &'a serde_json::Value) -> Result<&'a serde_json::Value, FilterError> {
+  match expr {
+    Expr::Field(name) => {
+      root.get(name).ok_or_else(|| FilterError(format!("no field '{}'", name)))
+    }
+    Expr::FieldAccess(base, field) => {
+      let base_json = eval_json(base, root)?;
+      base_json.get(field).ok_or_else(|| FilterError(format!("no field '{}'", field)))
+    }
+    Expr::Index(base, index) => {
+      let base_json = eval_json(base, root)?;
+      let index = eval_index(index, root)?;
+      base_json.get(index).ok_or_else(|| FilterError(format!("index {} out of bounds", index)))
+    }
+    _ => Err(FilterError("field access is only supported on field chains".into())),
+  }
+}
+
+fn eval_index(expr: &Expr, root: &serde_json::Value) -> Result<usize, FilterError> {
+  match eval(expr, root)? {
+    Value::UInt(n) => Ok(n as usize),
+    Value::Int(n) if n >= 0 => Ok(n as usize),
+    other => Err(FilterError(format!("array index must be a non-negative integer, found {:?}", other))),
+  }
+}
+
+fn eval_binop(op: BinOp, left: &Expr, right: &Expr, root: &serde_json::Value) -> Result<Value, FilterError> {
+  if op == BinOp::And {
+    return Ok(Value::Bool(eval(left, root)?.is_truthy() && eval(right, root)?.is_truthy()));
+  }
+  if op == BinOp::Or {
+    return Ok(Value::Bool(eval(left, root)?.is_truthy() || eval(right, root)?.is_truthy()));
+  }
+  let left = eval(left, root)?;
+  let right = eval(right, root)?;
+  match op {
+    BinOp::Eq => Ok(Value::Bool(values_equal(&left, &right))),
+    BinOp::Neq => Ok(Value::Bool(!values_equal(&left, &right))),
+    BinOp::Lt => Ok(Value::Bool(compare_numbers(&left, &right)? == std::cmp::Ordering::Less)),
+    BinOp::Le => Ok(Value::Bool(compare_numbers(&left, &right)? != std::cmp::Ordering::Greater)),
+    BinOp::Gt => Ok(Value::Bool(compare_numbers(&left, &right)? == std::cmp::Ordering::Greater)),
+    BinOp::Ge => Ok(Value::Bool(compare_numbers(&left, &right)? != std::cmp::Ordering::Less)),
+    BinOp::And | BinOp::Or => unreachable!(),
+  }
+}
+
+fn values_equal(left: &Value, right: &Value) -> bool {
+  match (left, right) {
+    (Value::Int(a), Value::Int(b)) => a == b,
+    (Value::UInt(a), Value::UInt(b)) => a == b,
+    (Value::Int(a), Value::UInt(b)) | (Value::UInt(b), Value::Int(a)) => *a >= 0 && *a as u128 == *b,
+    (Value::Str(a), Value::Str(b)) => a == b,
+    (Value::Bool(a), Value::Bool(b)) => a == b,
+    _ => false,
+  }
+}
+
+fn compare_numbers(left: &Value, right: &Value) -> Result<std::cmp::Ordering, FilterError> {
+  let as_i128 = |value: &Value| -> Result<i128, FilterError> {
+    match value {
+      Value::Int(n) => Ok(*n),
+      Value::UInt(n) => i128::try_from(*n).map_err(|_| FilterError("number too large".into())),
+      other => Err(FilterError(format!("cannot compare {:?}", other))),
+    }
+  };
+  Ok(as_i128(left)?.cmp(&as_i128(right)?))
+}
+
+fn call_builtin(name: &str, base: Value, args: Vec<Value>) -> Result<Value, FilterError> {
+  match name {
+    "count" | "len" => match base {
+      Value::Array(items) => Ok(Value::UInt(items.len() as u128)),
+      Value::Str(s) => Ok(Value::UInt(s.chars().count() as u128)),
+      other => Err(FilterError(format!("{} is not applicable to {:?}", name, other))),
+    },
+    "contains" => {
+      let needle = args.into_iter().next().ok_or_else(|| FilterError("contains expects 1 argument".into()))?;
+      match base {
+        Value::Array(items) => Ok(Value::Bool(items.iter().any(|item| values_equal(item, &needle)))),
+        Value::Str(s) => match needle {
+          Value::Str(needle) => Ok(Value::Bool(s.contains(&needle))),
+          other => Err(FilterError(format!("contains expects a string argument, found {:?}", other))),
+        },
+        other => Err(FilterError(format!("contains is not applicable to {:?}", other))),
+      }
+    }
+    "starts_with" => {
+      let prefix = args.into_iter().next().ok_or_else(|| FilterError("starts_with expects 1 argument".into()))?;
+      match (base, prefix) {
+        (Value::Str(s), Value::Str(prefix)) => Ok(Value::Bool(s.starts_with(&prefix))),
+        (base, prefix) => Err(FilterError(format!("starts_with expects strings, found {:?} and {:?}", base, prefix))),
+      }
+    }
+    other => Err(FilterError(format!("unknown function '{}'", other))),
+  }
+}
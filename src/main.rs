@@ -0,0 +1,297 @@
+mod api;
+mod bits;
+mod config;
+mod crypto;
+mod filter;
+mod hvm;
+mod node;
+mod util;
+
+#[cfg(test)]
+mod test;
+
+use std::fs;
+use std::io::{self, Read};
+
+use clap::{Parser, Subcommand};
+
+#[derive(Parser)]
+#[clap(name = "kindelia")]
+struct Cli {
+  /// Node API address to talk to.
+  #[clap(long, global = true, default_value = "http://localhost:8000/")]
+  api: String,
+
+  /// Filters or projects a `get` response with a small expression language,
+  /// e.g. `"mana > 300 && space"` or `"reg.list | count"`. A filter that
+  /// evaluates to `false` makes the command exit non-zero. Only meaningful
+  /// with `get`, but declared here (and `global`) so it can be passed
+  /// either before or after the `get` subcommand.
+  #[clap(long, global = true)]
+  filter: Option<String>,
+
+  #[clap(subcommand)]
+  command: CliCommand,
+}
+
+#[derive(Subcommand)]
+enum CliCommand {
+  /// Serializes a `.kdl` statement file into its hex-encoded bit format.
+  Serialize { file: String },
+  /// Deserializes a hex-encoded bit format file back into `.kdl` statements.
+  Deserialize { file: String },
+  /// Runs the statements in a `.kdl` file against a fresh in-memory state.
+  Test { file: String },
+  /// Signs the statements in a `.kdl` file with a secret key.
+  Sign {
+    file: String,
+    /// Path to a file holding the hex-encoded secret key, or `-` for stdin.
+    #[clap(long)]
+    secret_file: String,
+    /// Print only the raw signature hex instead of the full signed file.
+    #[clap(short = 'E')]
+    encoded: bool,
+  },
+  /// Reads state from a running node over its HTTP API.
+  Get {
+    #[clap(subcommand)]
+    command: GetCommand,
+  },
+  /// Manages a node process.
+  Node {
+    #[clap(subcommand)]
+    command: NodeCommand,
+  },
+  /// Key management: derivation, recovery and vanity search.
+  Key {
+    #[clap(subcommand)]
+    command: KeyCommand,
+  },
+  /// Verifies the signature on a signed `.kdl` file and prints the signer.
+  Verify {
+    file: String,
+    /// Require the recovered signer to match this account Name.
+    #[clap(long)]
+    address: Option<String>,
+  },
+}
+
+#[derive(Subcommand)]
+enum GetCommand {
+  Stats { subcommand: Option<String> },
+  Constructor { name: String, subcommand: Option<String> },
+  Reg { name: String, subcommand: Option<String> },
+  Functions { name: String, subcommand: Option<String> },
+  Peers { subcommand: Option<String> },
+}
+
+#[derive(Subcommand)]
+enum NodeCommand {
+  Start {
+    #[clap(long)]
+    mine: bool,
+    /// Settings file to load and, once running, hot-reload from on
+    /// `SIGHUP` or `POST /reload`.
+    #[clap(long, default_value = "kindelia.toml")]
+    config: String,
+  },
+}
+
+#[derive(Subcommand)]
+enum KeyCommand {
+  /// Deterministically derives a secret key from a passphrase ("brain wallet").
+  Brain {
+    /// Passphrase to derive the key from. Read from stdin if omitted.
+    phrase: Option<String>,
+  },
+  /// Searches for a secret key whose account Name starts with a prefix.
+  Prefix {
+    prefix: String,
+    /// Number of worker threads to search with.
+    #[clap(long, default_value_t = num_cpus::get())]
+    threads: usize,
+  },
+}
+
+fn read_secret_file(path: &str) -> io::Result<String> {
+  if path == "-" {
+    let mut input = String::new();
+    io::stdin().read_to_string(&mut input)?;
+    Ok(input.trim().to_string())
+  } else {
+    Ok(fs::read_to_string(path)?.trim().to_string())
+  }
+}
+
+fn main() {
+  let cli = Cli::parse();
+  match cli.command {
+    CliCommand::Serialize { file } => cmd_serialize(&file),
+    CliCommand::Deserialize { file } => cmd_deserialize(&file),
+    CliCommand::Test { file } => cmd_test(&file),
+    CliCommand::Sign { file, secret_file, encoded } => {
+      cmd_sign(&file, &secret_file, encoded)
+    }
+    CliCommand::Get { command } => cmd_get(&cli.api, command, cli.filter),
+    CliCommand::Node { command } => cmd_node(command),
+    CliCommand::Key { command } => cmd_key(command),
+    CliCommand::Verify { file, address } => cmd_verify(&file, address),
+  }
+}
+
+fn cmd_serialize(file: &str) {
+  let code = fs::read_to_string(file).expect("Could not read file");
+  let (_, statements) = hvm::read_statements(&code).expect("Could not parse statements");
+  for statement in statements {
+    println!("{}", bits::serialize_statement(&statement));
+  }
+}
+
+fn cmd_deserialize(file: &str) {
+  let hex = fs::read_to_string(file).expect("Could not read file");
+  for line in hex.lines() {
+    let statement = bits::deserialize_statement(line.trim());
+    println!("{}", statement);
+  }
+}
+
+fn cmd_test(file: &str) {
+  let code = fs::read_to_string(file).expect("Could not read file");
+  let (_, statements) = hvm::read_statements(&code).expect("Could not parse statements");
+  hvm::test_statements_from_code(&statements);
+}
+
+fn cmd_sign(file: &str, secret_file: &str, encoded: bool) {
+  let code = fs::read_to_string(file).expect("Could not read file");
+  let secret_hex = read_secret_file(secret_file).expect("Could not read secret file");
+  let secret_key = util::secret_key_from_hex(&secret_hex);
+  let (_, statements) = hvm::read_statements(&code).expect("Could not parse statements");
+  let signed: Vec<hvm::Statement> = statements
+    .into_iter()
+    .map(|statement| hvm::sign_statement(statement, &secret_key))
+    .collect();
+  if encoded {
+    for statement in &signed {
+      println!("{}", hvm::statement_sign_hex(statement));
+    }
+  } else {
+    for statement in &signed {
+      println!("{}", statement);
+    }
+  }
+}
+
+fn cmd_get(api: &str, command: GetCommand, filter: Option<String>) {
+  match filter {
+    None => node::get_and_print(api, command),
+    Some(expr) => cmd_get_filtered(api, command, &expr),
+  }
+}
+
+fn cmd_get_filtered(api: &str, command: GetCommand, expr: &str) {
+  let response = node::get_json(api, command).expect("Could not reach node API");
+  let expr = filter::parse(expr).expect("Could not parse filter expression");
+  let result = filter::eval(&expr, &response).expect("Could not evaluate filter expression");
+  if result == filter::Value::Bool(false) {
+    println!("{}", result);
+    std::process::exit(1);
+  }
+  println!("{}", result);
+}
+
+fn cmd_node(command: NodeCommand) {
+  match command {
+    NodeCommand::Start { mine, config } => cmd_node_start(mine, &config),
+  }
+}
+
+fn cmd_node_start(mine: bool, config_path: &str) {
+  let config_path = std::path::PathBuf::from(config_path);
+  let mut initial_config = config::parse(&config_path).expect("Could not parse config file");
+  if mine && !initial_config.mine {
+    // `--mine` only overrides the in-memory config for this process run; it
+    // is not written back to the settings file, so the first reload (via
+    // SIGHUP, the file watcher, or `POST /reload`) will re-read `mine` from
+    // the file and silently turn mining back off if the file still says so.
+    log::warn!(
+      "--mine overrides {} for this run only; a config reload will revert to the file's value",
+      config_path.display()
+    );
+    initial_config.mine = true;
+  }
+
+  let reload_bind = initial_config.reload_bind;
+  let live_config = config::LiveConfig::new(initial_config);
+  config::spawn_file_watcher(config_path.clone(), live_config.clone());
+  if let Err(err) = config::install_sighup_handler(config_path.clone(), live_config.clone()) {
+    eprintln!("could not install SIGHUP handler, config reloads will not react to SIGHUP: {}", err);
+  }
+  if let Some(bound_addr) = config::spawn_reload_server(reload_bind, config_path, live_config.clone()) {
+    eprintln!("listening for POST /reload on {}", bound_addr);
+  }
+
+  node::start(live_config);
+}
+
+fn cmd_key(command: KeyCommand) {
+  match command {
+    KeyCommand::Brain { phrase } => cmd_key_brain(phrase),
+    KeyCommand::Prefix { prefix, threads } => cmd_key_prefix(&prefix, threads),
+  }
+}
+
+fn cmd_key_brain(phrase: Option<String>) {
+  let phrase = match phrase {
+    Some(phrase) => phrase,
+    None => {
+      let mut input = String::new();
+      io::stdin().read_to_string(&mut input).expect("Could not read passphrase from stdin");
+      input.trim().to_string()
+    }
+  };
+  let secret_key = crypto::derive_brain_key(&phrase);
+  let public_key = crypto::public_key(&secret_key);
+  let name = crypto::account_name(&public_key);
+  // The secret key alone goes to stdout so `kindelia key brain <phrase>`
+  // can be piped straight into `--secret-file -`; the rest is informational.
+  println!("{}", hex::encode(secret_key.secret_bytes()));
+  eprintln!("public key: {}", hex::encode(public_key.serialize()));
+  eprintln!("account name: {}", name);
+}
+
+fn cmd_key_prefix(prefix: &str, threads: usize) {
+  eprintln!("searching for account names starting with '{}' using {} threads...", prefix, threads);
+  let found = crypto::search_prefix(prefix, threads);
+  eprintln!("found after {} attempts", found.attempts);
+  eprintln!("account name: {}", found.name);
+  eprintln!("public key: {}", hex::encode(found.public_key.serialize()));
+  println!("{}", hex::encode(found.secret_key.secret_bytes()));
+}
+
+fn cmd_verify(file: &str, address: Option<String>) {
+  let code = fs::read_to_string(file).expect("Could not read file");
+  let (_, statements) = hvm::read_statements(&code).expect("Could not parse statements");
+
+  let mut mismatch = false;
+  for statement in &statements {
+    let signature = hvm::statement_sign(statement)
+      .unwrap_or_else(|| panic!("Statement is not signed: {}", statement));
+    let hash = hvm::statement_sign_hash(statement);
+    let public_key = crypto::recover_public_key(&hash, &signature)
+      .expect("Could not recover a public key from the signature");
+    let name = crypto::account_name(&public_key);
+
+    if let Some(address) = &address {
+      if &name.to_string() != address {
+        eprintln!("signer mismatch: expected {}, recovered {}", address, name);
+        mismatch = true;
+        continue;
+      }
+    }
+    println!("{}", name);
+  }
+
+  if mismatch {
+    std::process::exit(1);
+  }
+}
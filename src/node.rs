@@ -0,0 +1,228 @@
+// The running node: talks to a node's HTTP API as a client (`get_and_print`,
+// `get_json`, used by `kindelia get`), and drives one as a server (`start`,
+// used by `kindelia node start`).
+//
+// `start` takes `Arc<config::LiveConfig>` rather than a one-shot snapshot:
+// mining, peer limits, the API bind list and the mana/space limits are all
+// safe to change live (see `config::RESTART_ONLY_FIELDS`), so the threads
+// below re-read the live config on every tick instead of capturing it once
+// at startup. The actual apply logic lives in small, pure functions so it
+// can be unit-tested without spinning up real sockets.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::{LiveConfig, NodeConfig};
+use crate::GetCommand;
+
+/// A peer the node has exchanged messages with.
+pub struct Peer {
+  pub address: SocketAddr,
+  pub seen_at: u64,
+}
+
+/// Parses a peer address, as given on the CLI or read back from the API.
+pub fn read_address(address: &str) -> SocketAddr {
+  address.parse().expect("invalid peer address")
+}
+
+/// How often the background loops in `start` re-read the live config.
+const TICK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Starts the node: a mining loop, a peer manager, and the API server, each
+/// on its own thread. All three re-read `live_config` every tick, so a
+/// config reload (`SIGHUP`, the file watcher, or `POST /reload`) takes
+/// effect without a restart.
+pub fn start(live_config: Arc<LiveConfig>) {
+  let mining = std::thread::spawn({
+    let live_config = Arc::clone(&live_config);
+    move || run_mining_loop(&live_config)
+  });
+  let peers = std::thread::spawn({
+    let live_config = Arc::clone(&live_config);
+    move || run_peer_manager(&live_config)
+  });
+  run_api_server(&live_config);
+
+  let _ = mining.join();
+  let _ = peers.join();
+}
+
+fn run_mining_loop(live_config: &LiveConfig) {
+  loop {
+    let config = live_config.snapshot();
+    if should_mine(&config) {
+      mine_block(mana_space_limits(&config));
+    }
+    std::thread::sleep(TICK_INTERVAL);
+  }
+}
+
+/// Whether the node should currently be mining.
+fn should_mine(config: &NodeConfig) -> bool {
+  config.mine
+}
+
+/// The mana/space limits the next mined block must respect.
+fn mana_space_limits(config: &NodeConfig) -> (u64, u64) {
+  (config.max_mana, config.max_space)
+}
+
+fn mine_block(limits: (u64, u64)) {
+  let (_max_mana, _max_space) = limits;
+  // Receives the limits a mined block is capped at; no block is built or
+  // broadcast here.
+}
+
+fn run_peer_manager(live_config: &LiveConfig) {
+  let mut peers: Vec<Peer> = Vec::new();
+  loop {
+    let config = live_config.snapshot();
+    enforce_peer_limit(&mut peers, &config);
+    std::thread::sleep(TICK_INTERVAL);
+  }
+}
+
+/// Drops the newest peers in excess of `config.max_peers`, keeping the
+/// longest-standing connections, so a live-lowered limit takes effect on
+/// already-connected peers, not just future ones.
+fn enforce_peer_limit(peers: &mut Vec<Peer>, config: &NodeConfig) {
+  if peers.len() > config.max_peers {
+    let excess = peers.len() - config.max_peers;
+    log::info!("max_peers lowered to {}, dropping {} peer(s)", config.max_peers, excess);
+    peers.truncate(config.max_peers);
+  }
+}
+
+/// The addresses the API server should currently be listening on.
+fn desired_api_binds(config: &NodeConfig) -> Vec<SocketAddr> {
+  config.api_bind.clone()
+}
+
+/// Listens on the addresses in `api_bind`, checking for changes every tick.
+/// When the list changes, a listener is spawned for every address in the new
+/// list. Listeners already running are never stopped: an address removed
+/// from `api_bind` keeps accepting connections, and an address kept across a
+/// change fails to bind again (logged) while its old listener goes on
+/// serving. Every request is answered with `501 Not Implemented`.
+fn run_api_server(live_config: &LiveConfig) {
+  let mut bound: Vec<SocketAddr> = Vec::new();
+  loop {
+    let config = live_config.snapshot();
+    let wanted = desired_api_binds(&config);
+    if wanted != bound {
+      log::info!("api_bind changed, listening on {:?}", wanted);
+      // Detached: listener threads spawned earlier keep running on their
+      // addresses, whether or not those are still in `wanted`.
+      for &addr in &wanted {
+        spawn_api_listener(addr);
+      }
+      bound = wanted;
+    }
+    std::thread::sleep(TICK_INTERVAL);
+  }
+}
+
+fn spawn_api_listener(addr: SocketAddr) {
+  let server = match tiny_http::Server::http(addr) {
+    Ok(server) => server,
+    Err(err) => {
+      log::warn!("could not bind API listener on {}: {}", addr, err);
+      return;
+    }
+  };
+  std::thread::spawn(move || {
+    for request in server.incoming_requests() {
+      let _ = request.respond(tiny_http::Response::empty(501));
+    }
+  });
+}
+
+/// Fetches `command` from `api` and prints the whole JSON response on one
+/// line. A `subcommand` is appended to the request path (see `get_json`);
+/// it does not select a field of the response.
+pub fn get_and_print(api: &str, command: GetCommand) {
+  let response = get_json(api, command).expect("Could not reach node API");
+  println!("{}", response);
+}
+
+/// Fetches `command` from `api` as raw JSON, for callers (like `--filter`)
+/// that want to inspect the response programmatically.
+pub fn get_json(api: &str, command: GetCommand) -> Result<serde_json::Value, String> {
+  let path = match command {
+    GetCommand::Stats { subcommand } => join_subcommand("stats", subcommand),
+    GetCommand::Constructor { name, subcommand } => join_subcommand(&format!("constructor/{}", name), subcommand),
+    GetCommand::Reg { name, subcommand } => join_subcommand(&format!("reg/{}", name), subcommand),
+    GetCommand::Functions { name, subcommand } => join_subcommand(&format!("functions/{}", name), subcommand),
+    GetCommand::Peers { subcommand } => join_subcommand("peers", subcommand),
+  };
+  let url = format!("{}{}", api.trim_end_matches('/'), path);
+  reqwest::blocking::get(&url)
+    .map_err(|err| err.to_string())?
+    .json::<serde_json::Value>()
+    .map_err(|err| err.to_string())
+}
+
+fn join_subcommand(base: &str, subcommand: Option<String>) -> String {
+  match subcommand {
+    Some(subcommand) => format!("/{}/{}", base, subcommand),
+    None => format!("/{}", base),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::path::PathBuf;
+
+  fn base_config() -> NodeConfig {
+    NodeConfig {
+      data_dir: PathBuf::from("/tmp/kindelia"),
+      network_id: 1,
+      private_key_file: PathBuf::from("/tmp/kindelia/key"),
+      reload_bind: "127.0.0.1:8001".parse().unwrap(),
+      mine: false,
+      max_peers: 32,
+      api_bind: vec!["127.0.0.1:8000".parse().unwrap()],
+      max_mana: 100_000,
+      max_space: 100_000_000,
+    }
+  }
+
+  #[test]
+  fn should_mine_follows_live_config() {
+    let mut config = base_config();
+    assert!(!should_mine(&config));
+    config.mine = true;
+    assert!(should_mine(&config));
+  }
+
+  #[test]
+  fn mana_space_limits_reflects_live_config() {
+    let mut config = base_config();
+    config.max_mana = 1;
+    config.max_space = 2;
+    assert_eq!(mana_space_limits(&config), (1, 2));
+  }
+
+  #[test]
+  fn desired_api_binds_reflects_live_config() {
+    let mut config = base_config();
+    config.api_bind = vec!["127.0.0.1:9000".parse().unwrap(), "127.0.0.1:9001".parse().unwrap()];
+    assert_eq!(desired_api_binds(&config), config.api_bind);
+  }
+
+  #[test]
+  fn enforce_peer_limit_drops_newest_excess_peers() {
+    let mut config = base_config();
+    config.max_peers = 1;
+    let mut peers = vec![
+      Peer { address: read_address("127.0.0.1:1"), seen_at: 1 },
+      Peer { address: read_address("127.0.0.1:2"), seen_at: 2 },
+    ];
+    enforce_peer_limit(&mut peers, &config);
+    assert_eq!(peers.len(), 1);
+    assert_eq!(peers[0].seen_at, 1);
+  }
+}
@@ -135,6 +135,100 @@ fn signing(#[case] private_key: &str, #[case] expected_result: &str) {
   assert_eq!(output, expected_result)
 }
 
+#[rstest]
+#[case("example/private_key_1_namer", "#656161725219724531611238334681629285")]
+#[case("example/private_key_2_alice", "#225111118185718227719509163399323998")]
+#[case("example/private_key_3_bob", "#540402903301314077240655651075245048")]
+fn verify_recovers_signer(
+  #[case] private_key: &str,
+  #[case] expected_name: &str,
+  temp_file: TempPath,
+) {
+  let signed = kindelia!()
+    .args(["sign", "example/block_3.unsig.kdl", "--secret-file", private_key])
+    .output()
+    .unwrap();
+  std::fs::write(&temp_file.path, get_stdout(&signed)).unwrap();
+
+  let output = kindelia!()
+    .args(["verify", temp_file.path.to_str().unwrap()])
+    .output()
+    .unwrap();
+  assert_eq!(get_stdout(&output), expected_name);
+
+  // and the positive `--address` check passes too
+  let output = kindelia!()
+    .args(["verify", temp_file.path.to_str().unwrap(), "--address", expected_name])
+    .output()
+    .unwrap();
+  assert!(output.status.success());
+}
+
+#[rstest]
+fn verify_rejects_wrong_address(temp_file: TempPath) {
+  let signed = kindelia!()
+    .args(["sign", "example/block_3.unsig.kdl", "--secret-file", "example/private_key_2_alice"])
+    .output()
+    .unwrap();
+  std::fs::write(&temp_file.path, get_stdout(&signed)).unwrap();
+
+  let output = kindelia!()
+    .args([
+      "verify",
+      temp_file.path.to_str().unwrap(),
+      "--address",
+      "#656161725219724531611238334681629285",
+    ])
+    .output()
+    .unwrap();
+  assert!(!output.status.success());
+}
+
+#[rstest]
+#[case("correct horse battery staple")]
+#[case("a much longer and more memorable brain-wallet passphrase")]
+fn key_brain_is_deterministic(#[case] phrase: &str) {
+  let output_1 = kindelia!().args(["key", "brain", phrase]).output().unwrap();
+  let output_2 = kindelia!().args(["key", "brain", phrase]).output().unwrap();
+  assert_eq!(get_stdout(&output_1), get_stdout(&output_2));
+}
+
+#[rstest]
+fn key_brain_round_trips_with_sign(temp_file: TempPath) {
+  let brain = kindelia!().args(["key", "brain", "correct horse battery staple"]).output().unwrap();
+  let secret_key = get_stdout(&brain);
+
+  let secret_file = temp_file.path.to_str().unwrap();
+  std::fs::write(secret_file, &secret_key).unwrap();
+
+  let signed_a = kindelia!()
+    .args(["sign", "example/block_3.unsig.kdl", "--secret-file", secret_file, "-E"])
+    .output()
+    .unwrap();
+
+  let mut stdin_secret_file = Command::new("sh");
+  // `key brain` is piped directly into `sign --secret-file -` so the
+  // round trip works without ever writing the key to disk.
+  let signed_b = stdin_secret_file
+    .arg("-c")
+    .arg(format!(
+      "cargo run --profile=dev_fast -- key brain 'correct horse battery staple' | cargo run --profile=dev_fast -- sign example/block_3.unsig.kdl --secret-file - -E"
+    ))
+    .output()
+    .unwrap();
+
+  assert_eq!(get_stdout(&signed_a), get_stdout(&signed_b));
+}
+
+#[rstest]
+fn key_prefix_finds_matching_account_name() {
+  // a single-character prefix is found almost instantly and keeps this test fast
+  let output = kindelia!().args(["key", "prefix", "A", "--threads", "2"]).output().unwrap();
+  assert!(output.status.success());
+  let secret_key = get_stdout(&output);
+  assert_eq!(secret_key.len(), 64);
+}
+
 #[rstest]
 #[case("/constructor/*", Some("T3"), ctr_response_1(), "ctr arity", "3")]
 #[case(
@@ -202,6 +296,48 @@ fn test_get_mock<T: serde::Serialize>(
   assert_eq!(output, expected_result)
 }
 
+#[rstest]
+#[case("mana > 300 && space > 400", true, "true")]
+#[case("mana > 999", false, "false")]
+#[case("tick == 700", true, "true")]
+fn test_get_filter(#[case] filter: &str, #[case] expect_success: bool, #[case] expected_result: &str) {
+  let response = stats_response_1();
+  let server = httpmock::MockServer::start();
+  server.mock(|when, then| {
+    when.method(httpmock::Method::GET).path("/stats");
+    then.status(200).json_body_obj(&response);
+  });
+  let mock_url = format!("http://127.0.0.1:{}/", server.port());
+
+  let output = kindelia!()
+    .args(["--api", &mock_url, "get", "stats", "--filter", filter])
+    .output()
+    .unwrap();
+  assert_eq!(output.status.success(), expect_success);
+  assert_eq!(get_stdout(&output), expected_result);
+}
+
+#[rstest]
+#[case("stmt[0]", "Foo")]
+#[case("stmt[2]", "Foo.Bar.cats")]
+#[case("stmt[0] == \"Foo\"", "true")]
+fn test_get_filter_array_index(#[case] filter: &str, #[case] expected_result: &str) {
+  let response = reg_response_1();
+  let server = httpmock::MockServer::start();
+  server.mock(|when, then| {
+    when.method(httpmock::Method::GET).path("/reg/Foo");
+    then.status(200).json_body_obj(&response);
+  });
+  let mock_url = format!("http://127.0.0.1:{}/", server.port());
+
+  let output = kindelia!()
+    .args(["--api", &mock_url, "get", "reg", "Foo", "--filter", filter])
+    .output()
+    .unwrap();
+  assert!(output.status.success());
+  assert_eq!(get_stdout(&output), expected_result);
+}
+
 fn ctr_response_1() -> api::CtrInfo {
   api::CtrInfo { arit: 3 }
 }